@@ -1,7 +1,6 @@
-use std::{collections::HashMap, net::TcpStream, sync::{Arc, Mutex}};
-use std::io::Write;
+use std::{collections::HashMap, sync::{Arc, Mutex}};
 
-use crate::{command_strategy::CommandStrategy, db::db::Redis, session::session::Session, RedisConfig};
+use crate::{command_strategy::CommandStrategy, db::db::Redis, pubsub::PubSub, replication::Replication, resp::Command, session::session::Session, tls::ClientStream, RedisConfig};
 
 /*
  * Echo 命令
@@ -11,13 +10,23 @@ pub struct EchoCommand {}
 impl CommandStrategy for EchoCommand {
     fn execute(
         &self,
-        stream: &mut TcpStream,
-        fragments: &Vec<&str>,
+        _stream: &mut ClientStream,
+        args: &Command,
         _redis: &Arc<Mutex<Redis>>,
         _redis_config: &Arc<RedisConfig>,
         _sessions: &Arc<Mutex<HashMap<String, Session>>>,
+        _pubsub: &Arc<PubSub>,
+        _replication: &Arc<Replication>,
+        out: &mut Vec<u8>,
     ) {
-        let response = format!("+{}\r\n", fragments[4]);
-        stream.write(response.as_bytes()).unwrap();
+        if args.len() < 2 {
+            out.extend_from_slice(b"-ERR wrong number of arguments for 'echo' command\r\n");
+            return;
+        }
+
+        let value = &args[1];
+        out.extend_from_slice(format!("${}\r\n", value.len()).as_bytes());
+        out.extend_from_slice(value);
+        out.extend_from_slice(b"\r\n");
     }
 }
\ No newline at end of file
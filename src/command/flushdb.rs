@@ -1,6 +1,5 @@
-use std::{collections::HashMap, net::TcpStream, sync::{Arc, Mutex}};
-use std::io::Write;
-use crate::{command_strategy::CommandStrategy, db::db::Redis, session::session::Session, RedisConfig};
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+use crate::{command_strategy::CommandStrategy, db::db::Redis, pubsub::PubSub, replication::Replication, resp::Command, session::session::Session, tls::ClientStream, RedisConfig};
 
 /*
  * FlushDb 命令
@@ -10,11 +9,14 @@ pub struct FlushDbCommand {}
 impl CommandStrategy for FlushDbCommand {
     fn execute(
         &self,
-        stream: &mut TcpStream,
-        _fragments: &Vec<&str>,
+        stream: &mut ClientStream,
+        _args: &Command,
         redis: &Arc<Mutex<Redis>>,
         _redis_config: &Arc<RedisConfig>,
         sessions: &Arc<Mutex<HashMap<String, Session>>>,
+        _pubsub: &Arc<PubSub>,
+        _replication: &Arc<Replication>,
+        out: &mut Vec<u8>,
     ) {
         let mut redis_ref = redis.lock().unwrap();
 
@@ -26,8 +28,8 @@ impl CommandStrategy for FlushDbCommand {
                 return;
             }
         };
-        
+
         redis_ref.flush_db(db_index);
-        stream.write(b"+OK\r\n").unwrap(); 
+        out.extend_from_slice(b"+OK\r\n");
     }
 }
\ No newline at end of file
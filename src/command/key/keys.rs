@@ -0,0 +1,52 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+
+use crate::{command_strategy::CommandStrategy, db::db::Redis, pubsub::PubSub, replication::Replication, resp::Command, session::session::Session, tls::ClientStream, RedisConfig};
+
+/*
+ * Keys 命令
+ *
+ * 列出当前数据库里匹配 pattern 的所有键。命名空间前缀（`--namespace`）
+ * 对这个命令完全透明：匹配和返回都发生在 `Redis::keys` 已经替调用方
+ * 去掉前缀之后，客户端看到的是自己当初传进去的键名，感知不到内部的
+ * 隔离前缀。
+ */
+pub struct KeysCommand {}
+
+impl CommandStrategy for KeysCommand {
+    fn execute(
+        &self,
+        stream: &mut ClientStream,
+        args: &Command,
+        redis: &Arc<Mutex<Redis>>,
+        _redis_config: &Arc<RedisConfig>,
+        sessions: &Arc<Mutex<HashMap<String, Session>>>,
+        _pubsub: &Arc<PubSub>,
+        _replication: &Arc<Replication>,
+        out: &mut Vec<u8>,
+    ) {
+        if args.len() != 2 {
+            out.extend_from_slice(b"-ERR wrong number of arguments for 'keys' command\r\n");
+            return;
+        }
+
+        let db_index = {
+            let sessions_ref = sessions.lock().unwrap();
+            if let Some(session) = sessions_ref.get(&stream.peer_addr().unwrap().to_string()) {
+                session.get_selected_database()
+            } else {
+                return;
+            }
+        };
+
+        let pattern = &args[1];
+        let redis_ref = redis.lock().unwrap();
+        let matched = redis_ref.keys(db_index, pattern);
+
+        out.extend_from_slice(format!("*{}\r\n", matched.len()).as_bytes());
+        for key in matched {
+            out.extend_from_slice(format!("${}\r\n", key.len()).as_bytes());
+            out.extend_from_slice(&key);
+            out.extend_from_slice(b"\r\n");
+        }
+    }
+}
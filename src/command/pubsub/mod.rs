@@ -0,0 +1,3 @@
+pub mod publish;
+pub mod subscribe;
+pub mod unsubscribe;
@@ -0,0 +1,39 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+
+use crate::{
+    command_strategy::CommandStrategy, db::db::Redis, pubsub::PubSub, replication::Replication,
+    resp::Command, session::session::Session, tls::ClientStream, RedisConfig,
+};
+
+/*
+ * Publish 命令
+ *
+ * 把消息投递给频道的所有订阅者，回复实际收到消息的订阅者数量。
+ */
+pub struct PublishCommand {}
+
+impl CommandStrategy for PublishCommand {
+    fn execute(
+        &self,
+        _stream: &mut ClientStream,
+        args: &Command,
+        _redis: &Arc<Mutex<Redis>>,
+        _redis_config: &Arc<RedisConfig>,
+        _sessions: &Arc<Mutex<HashMap<String, Session>>>,
+        pubsub: &Arc<PubSub>,
+        _replication: &Arc<Replication>,
+        out: &mut Vec<u8>,
+    ) {
+        if args.len() < 3 {
+            out.extend_from_slice(b"-ERR wrong number of arguments for 'publish' command\r\n");
+            return;
+        }
+
+        let channel_name = String::from_utf8_lossy(&args[1]).to_string();
+        let message = &args[2];
+
+        let delivered = pubsub.publish(&channel_name, message);
+
+        out.extend_from_slice(format!(":{}\r\n", delivered).as_bytes());
+    }
+}
@@ -0,0 +1,107 @@
+use std::io::Write;
+use std::sync::mpsc;
+use std::thread;
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+
+use crate::{
+    command_strategy::CommandStrategy,
+    db::db::Redis,
+    pubsub::{PubSub, SubscriberHandle},
+    replication::Replication,
+    resp::Command,
+    session::session::Session,
+    tls::ClientStream,
+    RedisConfig,
+};
+
+/*
+ * Subscribe 命令
+ *
+ * 第一次 SUBSCRIBE 时为当前连接懒创建一条写线程：它阻塞在
+ * `mpsc::Receiver` 上，把其他连接 PUBLISH 推送过来的消息写到本连接自己
+ * clone 出来的连接句柄上，这样即便本连接正阻塞在自己的
+ * `stream.read` 上，推送也能及时送达。
+ *
+ * 确认回复不走 `out`——`out` 要等这一次 read 里所有流水线命令都处理完
+ * 才会被主循环统一 flush，而 `pubsub.subscribe()` 一登记上句柄，别的
+ * 连接的 PUBLISH 就可能立刻通过这同一条写线程把 `message` 推过来，
+ * 抢在 `out` flush 之前到达客户端，造出订阅确认还没到、推送先到的
+ * 错序。所以这里把确认回复也送进同一条 `sender`，并且先发确认、
+ * 后登记句柄：`mpsc::Sender::send` 在多个发送方之间保序，只要确认
+ * 入队早于 `pubsub.subscribe()` 让该句柄对其他线程可见，后续任何
+ * PUBLISH 消息就只能排在确认后面。
+ */
+pub struct SubscribeCommand {}
+
+impl CommandStrategy for SubscribeCommand {
+    fn execute(
+        &self,
+        stream: &mut ClientStream,
+        args: &Command,
+        _redis: &Arc<Mutex<Redis>>,
+        _redis_config: &Arc<RedisConfig>,
+        sessions: &Arc<Mutex<HashMap<String, Session>>>,
+        pubsub: &Arc<PubSub>,
+        _replication: &Arc<Replication>,
+        _out: &mut Vec<u8>,
+    ) {
+        let session_id = stream.peer_addr().unwrap().to_string();
+
+        let sender = {
+            let mut sessions_ref = sessions.lock().unwrap();
+            let session = match sessions_ref.get_mut(&session_id) {
+                Some(session) => session,
+                None => return,
+            };
+
+            match session.get_publish_sender() {
+                Some(sender) => sender,
+                None => {
+                    let (tx, rx) = mpsc::channel::<Vec<u8>>();
+                    let writer_stream = stream.try_clone().unwrap();
+                    thread::spawn(move || writer_loop(writer_stream, rx));
+                    session.set_publish_sender(tx.clone());
+                    tx
+                }
+            }
+        };
+
+        for channel in &args[1..] {
+            let channel_name = String::from_utf8_lossy(channel).to_string();
+
+            let subscription_count = {
+                let mut sessions_ref = sessions.lock().unwrap();
+                let session = sessions_ref.get_mut(&session_id).unwrap();
+                session.add_subscription(channel_name.clone());
+                session.subscription_count()
+            };
+
+            let confirmation = format!(
+                "*3\r\n$9\r\nsubscribe\r\n${}\r\n{}\r\n:{}\r\n",
+                channel_name.len(),
+                channel_name,
+                subscription_count
+            );
+            if sender.send(confirmation.into_bytes()).is_err() {
+                return;
+            }
+
+            pubsub.subscribe(
+                channel_name.clone(),
+                SubscriberHandle {
+                    session_id: session_id.clone(),
+                    sender: sender.clone(),
+                },
+            );
+        }
+    }
+}
+
+/// 每个订阅连接独占一条写线程，串行地把推送消息落到它自己的 socket 上
+fn writer_loop(mut stream: ClientStream, rx: mpsc::Receiver<Vec<u8>>) {
+    while let Ok(message) = rx.recv() {
+        if stream.write_all(&message).is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+
+use crate::{
+    command_strategy::CommandStrategy, db::db::Redis, pubsub::PubSub, replication::Replication,
+    resp::Command, session::session::Session, tls::ClientStream, RedisConfig,
+};
+
+/*
+ * Unsubscribe 命令
+ *
+ * 不带参数时退订当前连接的所有频道，否则只退订列出的频道。
+ */
+pub struct UnsubscribeCommand {}
+
+impl CommandStrategy for UnsubscribeCommand {
+    fn execute(
+        &self,
+        stream: &mut ClientStream,
+        args: &Command,
+        _redis: &Arc<Mutex<Redis>>,
+        _redis_config: &Arc<RedisConfig>,
+        sessions: &Arc<Mutex<HashMap<String, Session>>>,
+        pubsub: &Arc<PubSub>,
+        _replication: &Arc<Replication>,
+        out: &mut Vec<u8>,
+    ) {
+        let session_id = stream.peer_addr().unwrap().to_string();
+
+        let channels: Vec<String> = if args.len() > 1 {
+            args[1..]
+                .iter()
+                .map(|channel| String::from_utf8_lossy(channel).to_string())
+                .collect()
+        } else {
+            let sessions_ref = sessions.lock().unwrap();
+            match sessions_ref.get(&session_id) {
+                Some(session) => session.subscriptions(),
+                None => return,
+            }
+        };
+
+        for channel_name in channels {
+            pubsub.unsubscribe(&channel_name, &session_id);
+
+            let subscription_count = {
+                let mut sessions_ref = sessions.lock().unwrap();
+                match sessions_ref.get_mut(&session_id) {
+                    Some(session) => {
+                        session.remove_subscription(&channel_name);
+                        session.subscription_count()
+                    }
+                    None => 0,
+                }
+            };
+
+            out.extend_from_slice(
+                format!(
+                    "*3\r\n$11\r\nunsubscribe\r\n${}\r\n{}\r\n:{}\r\n",
+                    channel_name.len(),
+                    channel_name,
+                    subscription_count
+                )
+                .as_bytes(),
+            );
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod replicaof;
+pub mod sync;
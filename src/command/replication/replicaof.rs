@@ -0,0 +1,71 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+
+use crate::{
+    command_strategy::CommandStrategy, db::db::Redis, pubsub::PubSub, replication,
+    replication::Replication, resp::Command, session::session::Session, tls::ClientStream,
+    RedisConfig,
+};
+
+/*
+ * Replicaof 命令（SLAVEOF 是它的别名）
+ *
+ * `REPLICAOF <host> <port>` 把当前实例切换为该地址的副本：开一条后台
+ * 线程连接过去，发 `SYNC` 做全量同步，再持续应用对方转发来的写命令。
+ * `REPLICAOF NO ONE` 把实例提升回主库——已经同步过去的数据继续保留，
+ * 只是不再接收新的写转发。
+ */
+pub struct ReplicaOfCommand {}
+
+impl CommandStrategy for ReplicaOfCommand {
+    fn execute(
+        &self,
+        _stream: &mut ClientStream,
+        args: &Command,
+        redis: &Arc<Mutex<Redis>>,
+        redis_config: &Arc<RedisConfig>,
+        sessions: &Arc<Mutex<HashMap<String, Session>>>,
+        pubsub: &Arc<PubSub>,
+        replication: &Arc<Replication>,
+        out: &mut Vec<u8>,
+    ) {
+        if args.len() != 3 {
+            out.extend_from_slice(b"-ERR wrong number of arguments for 'replicaof' command\r\n");
+            return;
+        }
+
+        let host = String::from_utf8_lossy(&args[1]).to_string();
+        let port_arg = String::from_utf8_lossy(&args[2]).to_string();
+
+        if host.eq_ignore_ascii_case("no") && port_arg.eq_ignore_ascii_case("one") {
+            replication.demote_to_master();
+            out.extend_from_slice(b"+OK\r\n");
+            return;
+        }
+
+        let port: u16 = match port_arg.parse() {
+            Ok(port) => port,
+            Err(_) => {
+                out.extend_from_slice(b"-ERR Invalid master port\r\n");
+                return;
+            }
+        };
+
+        // `promote_to_replica` 领到的 epoch 会交给新的同步线程；如果本
+        // 实例已经在扮演副本（重复 `REPLICAOF` 切主库），领到新 epoch 会让
+        // 旧的同步线程在下一轮自检时发现自己过时并退出，不会跟新线程一起
+        // 对着同一个 `Redis` 乱写。
+        let epoch = replication.promote_to_replica();
+        replication::start_replica(
+            host,
+            port,
+            Arc::clone(redis),
+            Arc::clone(redis_config),
+            Arc::clone(sessions),
+            Arc::clone(pubsub),
+            Arc::clone(replication),
+            epoch,
+        );
+
+        out.extend_from_slice(b"+OK\r\n");
+    }
+}
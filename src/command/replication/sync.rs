@@ -0,0 +1,34 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+
+use crate::{
+    command_strategy::CommandStrategy, db::db::Redis, pubsub::PubSub, replication,
+    replication::Replication, resp::Command, session::session::Session, tls::ClientStream,
+    RedisConfig,
+};
+
+/*
+ * Sync 命令
+ *
+ * 副本接入主库时发送的握手命令。主库把当前键空间编码成一串
+ * `SELECT`/`SET` 命令发给对方，随后把这条连接登记为副本——此后主库每
+ * 执行一条写命令，都会原样转发给它。回复不经过 `out`，而是统一走
+ * `replication::register_replica` 里起的那条写线程，和普通命令的应答
+ * 走不同的通道。
+ */
+pub struct SyncCommand {}
+
+impl CommandStrategy for SyncCommand {
+    fn execute(
+        &self,
+        stream: &mut ClientStream,
+        _args: &Command,
+        redis: &Arc<Mutex<Redis>>,
+        _redis_config: &Arc<RedisConfig>,
+        _sessions: &Arc<Mutex<HashMap<String, Session>>>,
+        _pubsub: &Arc<PubSub>,
+        replication: &Arc<Replication>,
+        _out: &mut Vec<u8>,
+    ) {
+        replication::register_replica(stream, redis, replication);
+    }
+}
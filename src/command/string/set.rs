@@ -1,8 +1,7 @@
 
-use std::{collections::HashMap, net::TcpStream, sync::{Arc, Mutex}};
-use std::io::Write;
+use std::{collections::HashMap, sync::{Arc, Mutex}};
 
-use crate::{command_strategy::CommandStrategy, db::db::Redis, session::session::Session, RedisConfig};
+use crate::{command_strategy::CommandStrategy, db::db::Redis, pubsub::PubSub, replication::Replication, resp::Command, session::session::Session, tls::ClientStream, RedisConfig};
 
 /*
  * Set 命令
@@ -12,12 +11,20 @@ pub struct SetCommand {}
 impl CommandStrategy for SetCommand {
     fn execute(
         &self,
-        stream: &mut TcpStream,
-        fragments: &Vec<&str>,
+        stream: &mut ClientStream,
+        args: &Command,
         redis: &Arc<Mutex<Redis>>,
         _redis_config: &Arc<RedisConfig>,
         sessions: &Arc<Mutex<HashMap<String, Session>>>,
+        _pubsub: &Arc<PubSub>,
+        _replication: &Arc<Replication>,
+        out: &mut Vec<u8>,
     ) {
+        if args.len() < 3 {
+            out.extend_from_slice(b"-ERR wrong number of arguments for 'set' command\r\n");
+            return;
+        }
+
         let mut redis_ref = redis.lock().unwrap();
 
         let db_index = {
@@ -29,20 +36,37 @@ impl CommandStrategy for SetCommand {
             }
         };
 
-        let key = fragments[4].to_string();
-        let value = fragments[6].to_string();
-        if fragments.len() > 8 {
-            if fragments[8].to_uppercase() == "PX" {
-                let ttl = fragments[10].parse::<i64>().unwrap();
-                redis_ref.set_with_ttl(db_index, key.clone(), value.clone(), ttl);
-            } else if fragments[8].to_uppercase() == "EX" {
-                let ttl = fragments[10].parse::<i64>().unwrap();
-                let ttl_millis = ttl * 1000;
-                redis_ref.set_with_ttl(db_index, key.clone(), value.clone(), ttl_millis);
+        let key = args[1].clone();
+        let value = args[2].clone();
+        if args.len() > 3 {
+            if args.len() < 5 {
+                drop(redis_ref);
+                out.extend_from_slice(b"-ERR syntax error\r\n");
+                return;
+            }
+
+            let option = String::from_utf8_lossy(&args[3]).to_uppercase();
+            let ttl = match String::from_utf8_lossy(&args[4]).parse::<i64>() {
+                Ok(ttl) => ttl,
+                Err(_) => {
+                    drop(redis_ref);
+                    out.extend_from_slice(b"-ERR value is not an integer or out of range\r\n");
+                    return;
+                }
+            };
+
+            if option == "PX" {
+                redis_ref.set_with_ttl(db_index, key, value, ttl);
+            } else if option == "EX" {
+                redis_ref.set_with_ttl(db_index, key, value, ttl * 1000);
+            } else {
+                drop(redis_ref);
+                out.extend_from_slice(b"-ERR syntax error\r\n");
+                return;
             }
         } else {
             redis_ref.set(db_index, key, value);
         }
-        stream.write(b"+OK\r\n").unwrap();
+        out.extend_from_slice(b"+OK\r\n");
     }
 }
\ No newline at end of file
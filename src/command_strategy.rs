@@ -0,0 +1,30 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+
+use crate::{
+    db::db::Redis, pubsub::PubSub, replication::Replication, resp::Command,
+    session::session::Session, tls::ClientStream, RedisConfig,
+};
+
+/*
+ * 命令策略接口
+ *
+ * `args` 是 `resp::parse` 产出的一条完整命令：`args[0]` 为命令名，
+ * 其余为参数，均为原始字节，二进制安全。回复不直接写回 `stream`，
+ * 而是追加到 `out`，以便一次 read 中解析出的多条流水线命令可以
+ * 合并成一次 `write` 发送（`SUBSCRIBE` 与 `SYNC` 例外，它们的回复经
+ * 各自连接的写线程发出，不写入 `out`）。`stream` 是 `ClientStream`，屏蔽了连接是否
+ * 经 TLS 加密的差异。
+ */
+pub trait CommandStrategy {
+    fn execute(
+        &self,
+        stream: &mut ClientStream,
+        args: &Command,
+        redis: &Arc<Mutex<Redis>>,
+        redis_config: &Arc<RedisConfig>,
+        sessions: &Arc<Mutex<HashMap<String, Session>>>,
+        pubsub: &Arc<PubSub>,
+        replication: &Arc<Replication>,
+        out: &mut Vec<u8>,
+    );
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::db::db_config::RedisConfig;
+
+/*
+ * 单个键存储的值
+ *
+ * @param data 原始字节，二进制安全
+ * @param expire_at 过期时间（毫秒时间戳），None 表示永不过期
+ */
+struct Entry {
+    data: Vec<u8>,
+    expire_at: Option<i64>,
+}
+
+/*
+ * Redis 核心存储引擎
+ *
+ * `databases` 按编号划分为互相独立的键空间，数量由
+ * `RedisConfig::databases` 决定，键与值均以 `Vec<u8>` 保存，
+ * 不对内容做任何编码假设。配置了 `RedisConfig::namespace` 时，所有
+ * 键在落盘前都会经 `namespaced_key` 透明加上前缀，读取与回显时再
+ * 原样去掉，调用方（各个 `CommandStrategy`）不需要关心这层前缀。
+ */
+pub struct Redis {
+    config: Arc<RedisConfig>,
+    databases: Vec<HashMap<Vec<u8>, Entry>>,
+}
+
+impl Redis {
+    pub fn new(config: Arc<RedisConfig>) -> Self {
+        let databases = (0..config.databases).map(|_| HashMap::new()).collect();
+        Self { config, databases }
+    }
+
+    /// 给客户端看到的键加上命名空间前缀，`namespace` 没配置时原样返回。
+    /// 所有写入/读取键空间的方法都要先过一遍这个函数，保证存进
+    /// `databases` 的键本身就是带前缀的，不用在每个方法里分别判断。
+    fn namespaced_key(&self, key: &[u8]) -> Vec<u8> {
+        match &self.config.namespace {
+            Some(namespace) => {
+                let mut namespaced = namespace.clone().into_bytes();
+                namespaced.extend_from_slice(key);
+                namespaced
+            }
+            None => key.to_vec(),
+        }
+    }
+
+    /// `namespaced_key` 的反函数，把内部存储的带前缀键还原成客户端本来
+    /// 传进来的样子，给 `KEYS` 这类要把键名回显给客户端的命令用。
+    fn strip_namespace(&self, key: Vec<u8>) -> Vec<u8> {
+        match &self.config.namespace {
+            Some(namespace) if key.starts_with(namespace.as_bytes()) => {
+                key[namespace.len()..].to_vec()
+            }
+            _ => key,
+        }
+    }
+
+    pub fn set(&mut self, db_index: usize, key: Vec<u8>, value: Vec<u8>) {
+        let key = self.namespaced_key(&key);
+        self.databases[db_index].insert(
+            key,
+            Entry {
+                data: value,
+                expire_at: None,
+            },
+        );
+    }
+
+    pub fn set_with_ttl(&mut self, db_index: usize, key: Vec<u8>, value: Vec<u8>, ttl_millis: i64) {
+        let key = self.namespaced_key(&key);
+        let expire_at = now_millis() + ttl_millis;
+        self.databases[db_index].insert(
+            key,
+            Entry {
+                data: value,
+                expire_at: Some(expire_at),
+            },
+        );
+    }
+
+    pub fn get(&self, db_index: usize, key: &[u8]) -> Option<Vec<u8>> {
+        let key = self.namespaced_key(key);
+        self.databases[db_index].get(&key).and_then(|entry| {
+            if let Some(expire_at) = entry.expire_at {
+                if expire_at <= now_millis() {
+                    return None;
+                }
+            }
+            Some(entry.data.clone())
+        })
+    }
+
+    pub fn flush_db(&mut self, db_index: usize) {
+        self.databases[db_index].clear();
+    }
+
+    /// 返回当前数据库里匹配 `pattern` 的所有键，已经去掉命名空间前缀，
+    /// 和客户端当初 `SET` 进来的样子一致。`pattern` 支持 `*`（任意长度，
+    /// 含空）和 `?`（单个字符）两种通配符，和 `KEYS` 的常见用法对齐；
+    /// 匹配本身也是在去掉前缀之后的键上做的，否则同一命名空间下
+    /// `namespace` 字面量本身也会参与匹配，跟客户端看到的键空间对不上。
+    pub fn keys(&self, db_index: usize, pattern: &[u8]) -> Vec<Vec<u8>> {
+        self.databases[db_index]
+            .keys()
+            .filter_map(|key| {
+                let stripped = self.strip_namespace(key.clone());
+                if glob_match(pattern, &stripped) {
+                    Some(stripped)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    pub fn load_aof(&mut self) {
+        // AOF 回放由持久化子系统负责，此处作为启动流程的占位保留
+    }
+
+    /// 把当前键空间重放成一串 `SELECT`/`SET` 命令，供 `SYNC` 在全量同步阶段
+    /// 发给新接入的副本；已过期的键直接跳过，带 TTL 的键换算成剩余毫秒数
+    /// 随 `SET ... PX <ttl>` 一并带过去。重放出来的命令会照常经过副本那边
+    /// 的 `SetCommand` 再执行一次 `namespaced_key`，所以这里要先把本地
+    /// 存储用的前缀去掉，不然命名空间会被叠加两次。
+    pub fn snapshot_commands(&self) -> Vec<Vec<Vec<u8>>> {
+        let now = now_millis();
+        let mut commands = Vec::new();
+
+        for (db_index, database) in self.databases.iter().enumerate() {
+            if database.is_empty() {
+                continue;
+            }
+
+            commands.push(vec![b"SELECT".to_vec(), db_index.to_string().into_bytes()]);
+
+            for (key, entry) in database {
+                let key = self.strip_namespace(key.clone());
+                match entry.expire_at {
+                    Some(expire_at) if expire_at <= now => continue,
+                    Some(expire_at) => commands.push(vec![
+                        b"SET".to_vec(),
+                        key,
+                        entry.data.clone(),
+                        b"PX".to_vec(),
+                        (expire_at - now).to_string().into_bytes(),
+                    ]),
+                    None => commands.push(vec![b"SET".to_vec(), key, entry.data.clone()]),
+                }
+            }
+        }
+
+        commands
+    }
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+/// 极简 glob 匹配，只认 `*`（任意长度，含空）和 `?`（单个任意字符），
+/// 够用即可，不追求 `[abc]` 这类字符集语法
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') => {
+            glob_match(&pattern[1..], text)
+                || (!text.is_empty() && glob_match(pattern, &text[1..]))
+        }
+        Some(b'?') => !text.is_empty() && glob_match(&pattern[1..], &text[1..]),
+        Some(&c) => !text.is_empty() && text[0] == c && glob_match(&pattern[1..], &text[1..]),
+    }
+}
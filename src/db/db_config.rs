@@ -2,18 +2,33 @@ use std::env;
 
 /*
  * Redis 配置
- * 
+ *
  * @param host 地址
  * @param port 端口
- * @param databases 初始化数据库 
+ * @param databases 初始化数据库
  * @param password 密码
+ * @param replicaof 以副本身份启动时要同步的主库地址，None 表示本实例是主库
+ * @param read_from_replica 副本是否在本地直接服务 GET/EXISTS 等只读命令
+ *        （写命令始终被拒绝），对应 redis-rs 集群模式下的 replica-read 选项
+ * @param tls_port 额外监听的 TLS 端口，None 表示不开启加密监听
+ * @param tls_cert_path TLS 证书文件路径（PEM），与 tls_port 搭配使用
+ * @param tls_key_path TLS 私钥文件路径（PEM），与 tls_port 搭配使用
+ * @param namespace 键空间前缀，None 表示不启用。设置后所有命令写入的键
+ *        都会被透明地加上这个前缀、读出时再原样去掉，从而让同一个实例
+ *        可以安全地给多个租户共用
  */
 pub struct RedisConfig {
     pub host: String,
     pub port: u16,
     pub password: Option<String>,
     pub databases: usize,
-    pub aof_file_path: Option<String>
+    pub aof_file_path: Option<String>,
+    pub replicaof: Option<(String, u16)>,
+    pub read_from_replica: bool,
+    pub tls_port: Option<u16>,
+    pub tls_cert_path: Option<String>,
+    pub tls_key_path: Option<String>,
+    pub namespace: Option<String>,
 }
 
 impl Default for RedisConfig {
@@ -23,7 +38,13 @@ impl Default for RedisConfig {
             databases: get_databases_or(16),
             port: get_port_or(6379),
             password: get_password_or(None),
-            aof_file_path: get_aof_file_path_or(None)
+            aof_file_path: get_aof_file_path_or(None),
+            replicaof: get_replicaof_or(None),
+            read_from_replica: get_read_from_replica_or(true),
+            tls_port: get_tls_port_or(None),
+            tls_cert_path: get_tls_cert_path_or(None),
+            tls_key_path: get_tls_key_path_or(None),
+            namespace: get_namespace_or(None),
         }
     }
 }
@@ -96,4 +117,115 @@ fn get_aof_file_path_or(default_aof_file_path: Option<String>) -> Option<String>
     } else {
         return default_aof_file_path;
     }
+}
+
+/*
+ * 获取 replicaof 参数
+ *
+ * @param default 默认值（None，即作为主库启动）
+ */
+fn get_replicaof_or(default: Option<(String, u16)>) -> Option<(String, u16)> {
+    let mut args = env::args().skip_while(|arg| arg != "--replicaof").take(3);
+    if args.next().is_none() {
+        return default;
+    }
+
+    let host = match args.next() {
+        Some(host) => host,
+        None => return default,
+    };
+    let port = match args.next() {
+        Some(port) => port
+            .parse()
+            .expect("'--replicaof' must have a '<host> <port>' value"),
+        None => return default,
+    };
+
+    Some((host, port))
+}
+
+/*
+ * 获取 read_from_replica 参数
+ *
+ * @param default 默认值（true）
+ */
+fn get_read_from_replica_or(default: bool) -> bool {
+    let mut args = env::args().skip_while(|arg| arg != "--read-from-replica").take(2);
+    if args.next().is_none() {
+        return default;
+    }
+
+    if let Some(arg) = args.next() {
+        return arg.parse().expect("'--read-from-replica' must have a value");
+    } else {
+        return default;
+    }
+}
+
+/*
+ * 获取 tls_port 参数
+ *
+ * @param default 默认值（None，即不开启 TLS 监听）
+ */
+fn get_tls_port_or(default: Option<u16>) -> Option<u16> {
+    let mut args = env::args().skip_while(|arg| arg != "--tls_port").take(2);
+    if args.next().is_none() {
+        return default;
+    }
+
+    if let Some(arg) = args.next() {
+        return Some(arg.parse().expect("'--tls_port' must have a value"));
+    } else {
+        return default;
+    }
+}
+
+/*
+ * 获取 TLS 证书文件路径参数
+ */
+fn get_tls_cert_path_or(default: Option<String>) -> Option<String> {
+    let mut args = env::args().skip_while(|arg| arg != "--tls_cert_path").take(2);
+    if args.next().is_none() {
+        return default;
+    }
+
+    if let Some(arg) = args.next() {
+        return Some(arg);
+    } else {
+        return default;
+    }
+}
+
+/*
+ * 获取 TLS 私钥文件路径参数
+ */
+fn get_tls_key_path_or(default: Option<String>) -> Option<String> {
+    let mut args = env::args().skip_while(|arg| arg != "--tls_key_path").take(2);
+    if args.next().is_none() {
+        return default;
+    }
+
+    if let Some(arg) = args.next() {
+        return Some(arg);
+    } else {
+        return default;
+    }
+}
+
+/*
+ * 获取 namespace 参数
+ *
+ * @param default 默认值（None，即不启用键空间前缀）
+ */
+fn get_namespace_or(default: Option<String>) -> Option<String> {
+    let mut args = env::args().skip_while(|arg| arg != "--namespace").take(2);
+    if args.next().is_none() {
+        return default;
+    }
+
+    if let Some(arg) = args.next() {
+        return Some(arg);
+    } else {
+        return default;
+    }
 }
\ No newline at end of file
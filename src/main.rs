@@ -1,13 +1,17 @@
 use std::collections::HashMap;
 use std::io::{Read, Write};
-use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::net::{SocketAddr, TcpListener};
 use std::sync::{Arc, Mutex};
 use std::thread;
 
 mod command;
 mod command_strategy;
 mod db;
+mod pubsub;
+mod replication;
+mod resp;
 mod session;
+mod tls;
 mod tools;
 
 use command::arr::llen::LlenCommand;
@@ -35,9 +39,32 @@ use command::flushdb::FlushDbCommand;
 use command::select::SelectCommand;
 use command_strategy::CommandStrategy;
 
+use command::pubsub::publish::PublishCommand;
+use command::pubsub::subscribe::SubscribeCommand;
+use command::pubsub::unsubscribe::UnsubscribeCommand;
+
+use command::replication::replicaof::ReplicaOfCommand;
+use command::replication::sync::SyncCommand;
+
 use crate::db::db::Redis;
 use crate::db::db_config::RedisConfig;
+use crate::pubsub::PubSub;
+use crate::replication::Replication;
 use crate::session::session::Session;
+use crate::tls::ClientStream;
+
+/*
+ * 会被主库转发给所有副本、并在本实例扮演副本时拒绝执行的写命令
+ */
+const WRITE_COMMANDS: &[&str] = &[
+    "set", "del", "expire", "lpush", "rpush", "append", "incr", "decr", "flushdb", "flushall",
+    "rename", "move",
+];
+
+/*
+ * 关闭 read_from_replica 的副本上，一律拒绝在本地直接服务的只读命令
+ */
+const READ_COMMANDS: &[&str] = &["get", "exists", "keys", "llen", "dbsize"];
 
 // Bootstrap.rs
 fn main() {
@@ -68,6 +95,8 @@ fn main() {
     let address = SocketAddr::from(([127, 0, 0, 1], port));
     let session_manager: Arc<Mutex<HashMap<String, Session>>> = Arc::new(Mutex::new(HashMap::new()));
     let redis = Arc::new(Mutex::new(Redis::new(redis_config.clone())));
+    let pubsub = Arc::new(PubSub::new());
+    let replication = Arc::new(Replication::new());
     let listener = TcpListener::bind(address).unwrap();
 
     /*
@@ -89,6 +118,76 @@ fn main() {
         }
     }
     
+    /*
+     * 以副本身份启动：连上配置里的主库，跑一次全量同步，再持续应用
+     * 它转发过来的写命令
+     */
+    if let Some((host, port)) = redis_config.replicaof.clone() {
+        let epoch = replication.promote_to_replica();
+        replication::start_replica(
+            host,
+            port,
+            Arc::clone(&redis),
+            Arc::clone(&redis_config),
+            Arc::clone(&session_manager),
+            Arc::clone(&pubsub),
+            Arc::clone(&replication),
+            epoch,
+        );
+    }
+
+    /*
+     * 并行起一个 TLS 监听器：配置了 tls_port/tls_cert_path/tls_key_path
+     * 时，在明文端口之外再开一个加密端口，两者共用同一份 redis/会话/
+     * pubsub/复制状态
+     */
+    if let (Some(tls_port), Some(tls_cert_path), Some(tls_key_path)) = (
+        redis_config.tls_port,
+        redis_config.tls_cert_path.clone(),
+        redis_config.tls_key_path.clone(),
+    ) {
+        let tls_config = tls::load_server_config(&tls_cert_path, &tls_key_path);
+        let tls_address = SocketAddr::from(([127, 0, 0, 1], tls_port));
+        let tls_listener = TcpListener::bind(tls_address).unwrap();
+
+        let redis = Arc::clone(&redis);
+        let redis_config = Arc::clone(&redis_config);
+        let session_manager = Arc::clone(&session_manager);
+        let pubsub = Arc::clone(&pubsub);
+        let replication = Arc::clone(&replication);
+
+        thread::spawn(move || {
+            for stream in tls_listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let tls_config = Arc::clone(&tls_config);
+                        let redis_clone = Arc::clone(&redis);
+                        let redis_config_clone = Arc::clone(&redis_config);
+                        let sessions_manager_clone = Arc::clone(&session_manager);
+                        let pubsub_clone = Arc::clone(&pubsub);
+                        let replication_clone = Arc::clone(&replication);
+                        thread::spawn(move || match tls::accept(stream, tls_config) {
+                            Ok(client_stream) => connection(
+                                client_stream,
+                                redis_clone,
+                                redis_config_clone,
+                                sessions_manager_clone,
+                                pubsub_clone,
+                                replication_clone,
+                            ),
+                            Err(e) => log::error!("TLS session setup failed: {}", e),
+                        });
+                    }
+                    Err(e) => {
+                        println!("error: {}", e);
+                    }
+                }
+            }
+        });
+
+        log::info!("TLS listener ready on port {}", tls_port);
+    }
+
     log::info!("Server initialized");
     log::info!("Ready to accept connections");
 
@@ -99,12 +198,16 @@ fn main() {
                 let redis_clone = Arc::clone(&redis);
                 let redis_config_clone = Arc::clone(&redis_config);
                 let sessions_manager_clone = Arc::clone(&session_manager);
+                let pubsub_clone = Arc::clone(&pubsub);
+                let replication_clone = Arc::clone(&replication);
                 thread::spawn(|| {
                     connection(
-                        stream,
+                        ClientStream::Plain(stream),
                         redis_clone,
                         redis_config_clone,
                         sessions_manager_clone,
+                        pubsub_clone,
+                        replication_clone,
                     )
                 });
             }
@@ -118,7 +221,7 @@ fn main() {
 /*
  * 初始化命令集合
  */
-fn init_command_strategies() -> HashMap<&'static str, Box<dyn CommandStrategy>> {
+pub(crate) fn init_command_strategies() -> HashMap<&'static str, Box<dyn CommandStrategy>> {
     let mut strategies: HashMap<&'static str, Box<dyn CommandStrategy>> = HashMap::new();
 
     strategies.insert("echo", Box::new(EchoCommand {}));
@@ -141,16 +244,24 @@ fn init_command_strategies() -> HashMap<&'static str, Box<dyn CommandStrategy>>
     strategies.insert("rpush", Box::new(RpushCommand {}));
     strategies.insert("incr", Box::new(IncrCommand {}));
     strategies.insert("decr", Box::new(DecrCommand {}));
+    strategies.insert("subscribe", Box::new(SubscribeCommand {}));
+    strategies.insert("unsubscribe", Box::new(UnsubscribeCommand {}));
+    strategies.insert("publish", Box::new(PublishCommand {}));
+    strategies.insert("sync", Box::new(SyncCommand {}));
+    strategies.insert("replicaof", Box::new(ReplicaOfCommand {}));
+    strategies.insert("slaveof", Box::new(ReplicaOfCommand {}));
 
     strategies
 }
 
 // 处理 Tcp 链接
 fn connection(
-    mut stream: TcpStream,
+    mut stream: ClientStream,
     redis: Arc<Mutex<Redis>>,
     redis_config: Arc<RedisConfig>,
     session_manager: Arc<Mutex<HashMap<String, Session>>>,
+    pubsub: Arc<PubSub>,
+    replication: Arc<Replication>,
 ) {
     
     /*
@@ -158,11 +269,15 @@ fn connection(
      *
      * @param command_strategies 命令集合
      * @param session_id 会话编号
-     * @param buff 消息容器
+     * @param read_buff 单次 read 的临时容器
+     * @param input_buff 持久化的输入缓冲区，跨 read 保留尚未解析完的字节
+     * @param cursor 已经解析到的位置，指向 input_buff 中下一条命令的起点
      */
     let command_strategies = init_command_strategies();
     let session_id = stream.peer_addr().unwrap().to_string();
-    let mut buff = [0; 512];
+    let mut read_buff = [0; 512];
+    let mut input_buff: Vec<u8> = Vec::new();
+    let mut cursor: usize = 0;
 
     {
         /*
@@ -175,7 +290,7 @@ fn connection(
     }
 
     'main: loop {
-        match stream.read(&mut buff) {
+        match stream.read(&mut read_buff) {
             Ok(size) => {
                 if size == 0 {
                     break 'main;
@@ -184,59 +299,133 @@ fn connection(
                 /*
                  * 解析命令
                  *
-                 * body: 消息体
-                 * fragments: 消息片段
-                 * command: 命令
+                 * 把新读到的字节追加到持久化的 input_buff 后面。像
+                 * redis-rs 这样的客户端会把多条命令（流水线）一次性写入
+                 * 同一个 TCP 报文，所以这里要反复从 cursor 处解析，直到
+                 * 拿到 Incomplete 为止，而不是只处理第一条就把剩下的丢掉。
+                 * 每条命令的回复先追加到 out_buff，等这次 read 里所有命令
+                 * 都处理完后再一次性 flush，减少系统调用次数。
                  */
+                input_buff.extend_from_slice(&read_buff[..size]);
 
-                let body = std::str::from_utf8(&buff[..size]).unwrap();
-                let fragments: Vec<&str> = body.split("\r\n").collect();
-                let command = fragments[2];
+                let mut out_buff: Vec<u8> = Vec::new();
+
+                loop {
+                    let command = match resp::parse(&input_buff[cursor..]) {
+                        Ok(resp::ParseResult::Complete(command, consumed)) => {
+                            cursor += consumed;
+                            command
+                        }
+                        Ok(resp::ParseResult::Incomplete) => {
+                            break;
+                        }
+                        Err(e) => {
+                            out_buff.extend_from_slice(format!("-ERR {}\r\n", e).as_bytes());
+                            input_buff.clear();
+                            cursor = 0;
+                            break;
+                        }
+                    };
+
+                    if command.is_empty() {
+                        out_buff.extend_from_slice(b"-ERR Protocol error: empty command\r\n");
+                        continue;
+                    }
+
+                    let command_name = String::from_utf8_lossy(&command[0]).to_lowercase();
+
+                    {
+                        /*
+                         * 安全认证【前置拦截】
+                         */
+                        let session_manager_ref = session_manager.lock().unwrap();
+                        let session = session_manager_ref.get(&session_id).unwrap();
+
+                        if redis_config.password != None && command_name != "auth" {
+                            if !session.get_authenticated() {
+                                out_buff.extend_from_slice(b"-ERR Authentication required\r\n");
+                                continue;
+                            }
+                        }
+                    }
 
-                {
                     /*
-                     * 安全认证【前置拦截】
+                     * 只读副本拦截
+                     *
+                     * 副本角色下写命令一律拒绝，跟 read_from_replica 无关
+                     * ——数据只能通过主库转发过来，本地直接写会和主库分叉。
+                     * read_from_replica 只决定 GET/EXISTS 这类读命令能不能
+                     * 在本地直接服务：关掉之后这些读也要被拒绝，逼客户端去
+                     * 连主库读，不然关掉这个开关就没有任何实际效果。
                      */
-                    let session_manager_ref = session_manager.lock().unwrap();
-                    let session = session_manager_ref.get(&session_id).unwrap();
-
-                    if redis_config.password != None && command != "auth" {
-                        if !session.get_authenticated() {
-                            let response = "-ERR Authentication required\r\n";
-                            stream.write(response.as_bytes()).unwrap();
-                            continue 'main; // 跳过当前循环
+                    if replication.is_replica() && WRITE_COMMANDS.contains(&command_name.as_str())
+                    {
+                        out_buff
+                            .extend_from_slice(b"-READONLY You can't write against a read only replica.\r\n");
+                        continue;
+                    }
+
+                    if !redis_config.read_from_replica
+                        && replication.is_replica()
+                        && READ_COMMANDS.contains(&command_name.as_str())
+                    {
+                        out_buff.extend_from_slice(
+                            b"-READONLY This replica is not configured to serve reads; see --read-from-replica.\r\n",
+                        );
+                        continue;
+                    }
+
+                    /*
+                     * 执行命令
+                     *
+                     * 利用策略模式，根据 command_name 获取具体实现，
+                     * 否则响应 PONG 内容。写命令执行成功后原样转发给所有
+                     * 已接入的副本；`select` 也要跟着转发——副本侧重放写
+                     * 命令用的是同一个 `Session`，如果看不到 `SELECT`，
+                     * 客户端切库之后的写就会被套用到副本上错误的数据库。
+                     *
+                     * TODO 将 所有会话 调整为 当前会话
+                     */
+                    if let Some(strategy) = command_strategies.get(command_name.as_str()) {
+                        strategy.execute(
+                            &mut stream,
+                            &command,
+                            &redis,
+                            &redis_config,
+                            &session_manager,
+                            &pubsub,
+                            &replication,
+                            &mut out_buff,
+                        );
+
+                        if WRITE_COMMANDS.contains(&command_name.as_str()) || command_name == "select" {
+                            replication.propagate(&command);
                         }
+                    } else {
+                        out_buff.extend_from_slice(b"+PONG\r\n");
                     }
                 }
 
-                /*
-                 * 执行命令
-                 *
-                 * 利用策略模式，根据 command 获取具体实现，
-                 * 否则响应 PONG 内容。
-                 *
-                 * TODO 将 所有会话 调整为 当前会话
-                 */
-                if let Some(strategy) = command_strategies.get(command) {
-                    strategy.execute(
-                        &mut stream,
-                        &fragments,
-                        &redis,
-                        &redis_config,
-                        &session_manager,
-                    );
-                } else {
-                    stream.write(b"+PONG\r\n").unwrap();
+                if cursor == input_buff.len() {
+                    input_buff.clear();
+                    cursor = 0;
+                }
+
+                if !out_buff.is_empty() {
+                    stream.write_all(&out_buff).unwrap();
                 }
             }
             Err(_e) => {
                 /*
                  * 销毁会话
                  *
-                 * @param session_id 会话编号
+                 * @param session_id 会话编号，顺带清理它在 PubSub 与
+                 *        Replication 里留下的订阅/同步句柄
                  */
                 let mut session_manager_ref = session_manager.lock().unwrap();
                 session_manager_ref.remove(&session_id);
+                pubsub.remove_session(&session_id);
+                replication.remove_replica(&session_id);
 
                 break 'main;
             }
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+
+/*
+ * 一个已订阅连接的投递句柄
+ *
+ * @param session_id 所属会话编号，退订/断线时用它定位并移除句柄
+ * @param sender 该连接写线程的另一端，PUBLISH 通过它把消息送到目标连接，
+ *               再由写线程落到各自的 TcpStream 上，从而不阻塞在自己的
+ *               `stream.read` 上的订阅者也能收到推送
+ */
+pub struct SubscriberHandle {
+    pub session_id: String,
+    pub sender: Sender<Vec<u8>>,
+}
+
+/*
+ * 发布/订阅注册表
+ *
+ * 按频道名维护一组订阅者句柄，SUBSCRIBE 时把当前连接的句柄挂到频道下，
+ * PUBLISH 时遍历该频道的所有句柄逐一投递。
+ */
+pub struct PubSub {
+    channels: Mutex<HashMap<String, Vec<SubscriberHandle>>>,
+}
+
+impl PubSub {
+    pub fn new() -> Self {
+        Self {
+            channels: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn subscribe(&self, channel: String, handle: SubscriberHandle) {
+        let mut channels = self.channels.lock().unwrap();
+        let handles = channels.entry(channel).or_insert_with(Vec::new);
+        // 同一连接重复订阅同一频道时只保留一个句柄，避免重复投递与重复计数
+        if !handles.iter().any(|h| h.session_id == handle.session_id) {
+            handles.push(handle);
+        }
+    }
+
+    pub fn unsubscribe(&self, channel: &str, session_id: &str) {
+        let mut channels = self.channels.lock().unwrap();
+        if let Some(handles) = channels.get_mut(channel) {
+            handles.retain(|handle| handle.session_id != session_id);
+        }
+    }
+
+    /// 向 `channel` 的所有订阅者投递一条 `message` 数组回复，返回成功
+    /// 投递到的接收者数量。
+    pub fn publish(&self, channel: &str, message: &[u8]) -> usize {
+        let channels = self.channels.lock().unwrap();
+        let handles = match channels.get(channel) {
+            Some(handles) => handles,
+            None => return 0,
+        };
+
+        let mut delivered = 0;
+        for handle in handles {
+            if handle.sender.send(encode_message(channel, message)).is_ok() {
+                delivered += 1;
+            }
+        }
+        delivered
+    }
+
+    /// 连接断开时清理它在所有频道里留下的订阅句柄
+    pub fn remove_session(&self, session_id: &str) {
+        let mut channels = self.channels.lock().unwrap();
+        for handles in channels.values_mut() {
+            handles.retain(|handle| handle.session_id != session_id);
+        }
+    }
+}
+
+fn encode_message(channel: &str, message: &[u8]) -> Vec<u8> {
+    let mut payload = format!("*3\r\n$7\r\nmessage\r\n${}\r\n", channel.len()).into_bytes();
+    payload.extend_from_slice(channel.as_bytes());
+    payload.extend_from_slice(b"\r\n");
+    payload.extend_from_slice(format!("${}\r\n", message.len()).as_bytes());
+    payload.extend_from_slice(message);
+    payload.extend_from_slice(b"\r\n");
+    payload
+}
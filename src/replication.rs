@@ -0,0 +1,276 @@
+use std::io::{self, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::db::db::Redis;
+use crate::db::db_config::RedisConfig;
+use crate::pubsub::PubSub;
+use crate::resp::{self, Command, ParseResult};
+use crate::session::session::Session;
+use crate::tls::ClientStream;
+use std::collections::HashMap;
+
+/*
+ * 一个已连接副本的转发句柄
+ *
+ * @param session_id 该副本连接的会话编号，断线时用它定位并移除句柄
+ * @param sender 副本连接写线程的另一端，主库每执行一条写命令就把它原样
+ *               转发到这里，再由写线程落到副本自己的 TcpStream 上
+ */
+struct ReplicaHandle {
+    session_id: String,
+    sender: Sender<Vec<u8>>,
+}
+
+/*
+ * 复制状态
+ *
+ * `replicas` 记录当前已接入的副本，供主库转发写命令；`is_replica`
+ * 标记本实例当前是否正在扮演副本角色（启动时由 `--replicaof` 决定，
+ * 运行期间可被 `REPLICAOF`/`SLAVEOF` 切换），只读拦截与命令转发都依赖
+ * 这个标记。
+ *
+ * `replica_epoch` 给每一次"开始扮演副本"的动作编号：`promote_to_replica`
+ * 领取新编号交给对应的后台同步线程，`demote_to_master` 或下一次
+ * `promote_to_replica` 都会把编号往前推一格。同步线程每轮都检查自己
+ * 领到的编号是否还是最新的，一旦不是就自行退出——否则 `REPLICAOF NO
+ * ONE` 或连续两次 `REPLICAOF` 都会把旧线程晾在原地继续套用旧主库的写
+ * 命令，和新状态互相打架。
+ */
+pub struct Replication {
+    replicas: Mutex<Vec<ReplicaHandle>>,
+    is_replica: AtomicBool,
+    replica_epoch: AtomicU64,
+}
+
+impl Replication {
+    pub fn new() -> Self {
+        Self {
+            replicas: Mutex::new(Vec::new()),
+            is_replica: AtomicBool::new(false),
+            replica_epoch: AtomicU64::new(0),
+        }
+    }
+
+    pub fn is_replica(&self) -> bool {
+        self.is_replica.load(Ordering::SeqCst)
+    }
+
+    /// 把本实例切换为副本角色，返回这次切换领到的 epoch，调用方把它原样
+    /// 交给 `start_replica` 起的后台同步线程，用来识别自己是否已经过时。
+    pub fn promote_to_replica(&self) -> u64 {
+        self.is_replica.store(true, Ordering::SeqCst);
+        self.replica_epoch.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    pub fn demote_to_master(&self) {
+        self.is_replica.store(false, Ordering::SeqCst);
+        self.replica_epoch.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// 供同步线程自检：`epoch` 是不是自己领到时的那个最新编号
+    fn is_current_epoch(&self, epoch: u64) -> bool {
+        self.replica_epoch.load(Ordering::SeqCst) == epoch
+    }
+
+    fn register(&self, session_id: String, sender: Sender<Vec<u8>>) {
+        let mut replicas = self.replicas.lock().unwrap();
+        replicas.push(ReplicaHandle { session_id, sender });
+    }
+
+    pub fn remove_replica(&self, session_id: &str) {
+        let mut replicas = self.replicas.lock().unwrap();
+        replicas.retain(|replica| replica.session_id != session_id);
+    }
+
+    /// 把 `command` 原样转发给所有已接入的副本，返回成功投递到的副本数量
+    pub fn propagate(&self, command: &Command) -> usize {
+        let replicas = self.replicas.lock().unwrap();
+        if replicas.is_empty() {
+            return 0;
+        }
+
+        let encoded = encode_command(command);
+        let mut delivered = 0;
+        for replica in replicas.iter() {
+            if replica.sender.send(encoded.clone()).is_ok() {
+                delivered += 1;
+            }
+        }
+        delivered
+    }
+}
+
+/// 把一条命令重新编码成 RESP 多行数组，用于全量同步快照与写命令转发
+pub fn encode_command(command: &Command) -> Vec<u8> {
+    let mut payload = format!("*{}\r\n", command.len()).into_bytes();
+    for arg in command {
+        payload.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+        payload.extend_from_slice(arg);
+        payload.extend_from_slice(b"\r\n");
+    }
+    payload
+}
+
+/// 每个副本连接独占一条写线程，串行地把快照与转发来的写命令落到它自己的
+/// socket 上，这样主库不会阻塞在某个慢副本的网络 I/O 上
+fn writer_loop(mut stream: ClientStream, rx: mpsc::Receiver<Vec<u8>>) {
+    while let Ok(message) = rx.recv() {
+        if stream.write_all(&message).is_err() {
+            break;
+        }
+    }
+}
+
+/// master 侧：接到 `SYNC` 后，把当前键空间编码成快照发给新接入的副本，
+/// 再把它登记到 `replication` 上以接收后续转发的写命令。
+///
+/// 快照与登记必须在同一次 `redis` 加锁期间内完成：如果中途放一次锁，
+/// 一条在这个缝隙里提交的并发写命令既赶不上这次快照（已经生成完了），
+/// 又因为还没登记到 `replication` 而收不到后续的转发，从此在这个副本
+/// 上永久缺失。
+pub fn register_replica(stream: &mut ClientStream, redis: &Arc<Mutex<Redis>>, replication: &Arc<Replication>) {
+    let session_id = stream.peer_addr().unwrap().to_string();
+    let (tx, rx) = mpsc::channel::<Vec<u8>>();
+    let writer_stream = stream.try_clone().unwrap();
+    thread::spawn(move || writer_loop(writer_stream, rx));
+
+    let redis_ref = redis.lock().unwrap();
+    for command in redis_ref.snapshot_commands() {
+        let _ = tx.send(encode_command(&command));
+    }
+    replication.register(session_id, tx);
+}
+
+/// replica 侧：连接到 `host:port` 的主库，发起 `SYNC`，然后持续把主库
+/// 流式传来的命令（全量快照 + 实时转发的写命令）应用到本地 `redis`。
+///
+/// 复用 `command_strategies`——即客户端命令用的同一套策略对象——而不是
+/// 重新发明一套命令解释逻辑，这样 `SET`/`DEL`/`EXPIRE`/`LPUSH` 等每多一个
+/// 写命令，复制这边自动跟着支持，不需要再维护一份影子实现。
+///
+/// `epoch` 是调用方从 `Replication::promote_to_replica` 领到的编号，每轮
+/// 循环都会跟 `replication` 当前的最新编号核对一次：一旦对不上（本实例
+/// 被 `REPLICAOF NO ONE` 提升回主库，或者又调用了一次 `REPLICAOF` 换了
+/// 新主库），这条线程就认输退出，不再继续套用旧主库转发来的写命令。
+pub fn start_replica(
+    host: String,
+    port: u16,
+    redis: Arc<Mutex<Redis>>,
+    redis_config: Arc<RedisConfig>,
+    sessions: Arc<Mutex<HashMap<String, Session>>>,
+    pubsub: Arc<PubSub>,
+    replication: Arc<Replication>,
+    epoch: u64,
+) {
+    thread::spawn(move || {
+        let stream = match TcpStream::connect((host.as_str(), port)) {
+            Ok(stream) => stream,
+            Err(e) => {
+                log::error!("replicaof: failed to connect to master {}:{}: {}", host, port, e);
+                return;
+            }
+        };
+
+        if let Err(e) = stream.set_read_timeout(Some(Duration::from_millis(200))) {
+            log::error!("replicaof: failed to set read timeout for {}:{}: {}", host, port, e);
+            return;
+        }
+        let mut stream = stream;
+
+        let session_id = stream.peer_addr().unwrap().to_string();
+        {
+            let mut sessions_ref = sessions.lock().unwrap();
+            sessions_ref.insert(session_id.clone(), Session::new());
+        }
+
+        if stream
+            .write_all(&encode_command(&vec![b"SYNC".to_vec()]))
+            .is_err()
+        {
+            log::error!("replicaof: failed to send SYNC to master {}:{}", host, port);
+            return;
+        }
+
+        let command_strategies = crate::init_command_strategies();
+        let mut client_stream = match stream.try_clone() {
+            Ok(clone) => ClientStream::Plain(clone),
+            Err(e) => {
+                log::error!("replicaof: failed to clone socket for {}:{}: {}", host, port, e);
+                return;
+            }
+        };
+        let mut read_buff = [0; 512];
+        let mut input_buff: Vec<u8> = Vec::new();
+        let mut cursor: usize = 0;
+
+        loop {
+            if !replication.is_current_epoch(epoch) {
+                log::info!(
+                    "replicaof: epoch superseded, stopping stale sync thread for {}:{}",
+                    host,
+                    port
+                );
+                // 不走下面的会话清理：连同一主库的新线程用的是同一个会话编号
+                return;
+            }
+
+            let size = match std::io::Read::read(&mut stream, &mut read_buff) {
+                Ok(0) => break,
+                Ok(size) => size,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => {
+                    continue;
+                }
+                Err(_) => break,
+            };
+
+            input_buff.extend_from_slice(&read_buff[..size]);
+
+            loop {
+                let command = match resp::parse(&input_buff[cursor..]) {
+                    Ok(ParseResult::Complete(command, consumed)) => {
+                        cursor += consumed;
+                        command
+                    }
+                    Ok(ParseResult::Incomplete) => break,
+                    Err(_) => {
+                        input_buff.clear();
+                        cursor = 0;
+                        break;
+                    }
+                };
+
+                if command.is_empty() {
+                    continue;
+                }
+
+                let command_name = String::from_utf8_lossy(&command[0]).to_lowercase();
+                if let Some(strategy) = command_strategies.get(command_name.as_str()) {
+                    let mut discarded_out = Vec::new();
+                    strategy.execute(
+                        &mut client_stream,
+                        &command,
+                        &redis,
+                        &redis_config,
+                        &sessions,
+                        &pubsub,
+                        &replication,
+                        &mut discarded_out,
+                    );
+                }
+            }
+
+            if cursor == input_buff.len() {
+                input_buff.clear();
+                cursor = 0;
+            }
+        }
+
+        sessions.lock().unwrap().remove(&session_id);
+        log::warn!("replicaof: lost connection to master {}:{}", host, port);
+    });
+}
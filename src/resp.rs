@@ -0,0 +1,182 @@
+use std::fmt;
+
+/*
+ * RESP（REdis Serialization Protocol）增量解析器
+ *
+ * 客户端请求总是以数组形式发送：
+ *
+ *   *<argc>\r\n
+ *   $<len>\r\n
+ *   <arg bytes>\r\n
+ *   ...
+ *
+ * `parse` 只负责从缓冲区的起始位置尝试解析出一条完整命令，本身不持有任何
+ * 状态；调用方负责维护输入缓冲区与读取游标，在得到 `Incomplete` 时保留
+ * 已读到的字节，等待下一次 `read` 补全后重新调用。
+ */
+
+/// 一条已解析的命令，`command[0]` 是命令名，其余是参数；均为原始字节，
+/// 不做任何编码假设，因此可以安全地承载二进制值或内嵌 `\r\n` 的 key。
+pub type Command = Vec<Vec<u8>>;
+
+#[derive(Debug)]
+pub enum ParseResult {
+    /// 解析出一条完整命令，以及它在输入缓冲区中消费掉的字节数
+    Complete(Command, usize),
+    /// 缓冲区中还没有一条完整的命令，等待更多数据
+    Incomplete,
+}
+
+#[derive(Debug)]
+pub struct ProtocolError(String);
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Protocol error: {}", self.0)
+    }
+}
+
+/// 多条批量参数个数上限，和 redis 本身的 `proto-max-multibulk-len` 默认值
+/// 对齐，防止 `*<很大的数>\r\n` 在 `Vec::with_capacity` 里直接要走几十 GB
+/// 内存——分配失败时 Rust 会直接 `abort()` 整个进程，不是能 `catch` 的 panic。
+const MAX_MULTIBULK_LEN: usize = 1024 * 1024;
+
+/// 单个参数体长度上限，和 redis 的 512MB 单值上限对齐
+const MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+
+pub fn parse(buf: &[u8]) -> Result<ParseResult, ProtocolError> {
+    let mut pos = 0;
+
+    let argc_line = match read_line(buf, &mut pos) {
+        Some(line) => line,
+        None => return Ok(ParseResult::Incomplete),
+    };
+    if argc_line.first() != Some(&b'*') {
+        return Err(ProtocolError(format!(
+            "expected '*', got {:?}",
+            argc_line.first()
+        )));
+    }
+    let argc = parse_usize(&argc_line[1..])?;
+    if argc > MAX_MULTIBULK_LEN {
+        return Err(ProtocolError(format!(
+            "invalid multibulk length {}",
+            argc
+        )));
+    }
+
+    let mut command = Vec::with_capacity(argc);
+    for _ in 0..argc {
+        let len_line = match read_line(buf, &mut pos) {
+            Some(line) => line,
+            None => return Ok(ParseResult::Incomplete),
+        };
+        if len_line.first() != Some(&b'$') {
+            return Err(ProtocolError(format!(
+                "expected '$', got {:?}",
+                len_line.first()
+            )));
+        }
+        let len = parse_usize(&len_line[1..])?;
+        if len > MAX_BULK_LEN {
+            return Err(ProtocolError(format!("invalid bulk length {}", len)));
+        }
+
+        // 参数体 + 结尾的 \r\n 还没有完整到达；len 已经被上面的上限挡住，
+        // 不会在 pos + len + 2 上溢出
+        if buf.len() < pos + len + 2 {
+            return Ok(ParseResult::Incomplete);
+        }
+        command.push(buf[pos..pos + len].to_vec());
+        pos += len + 2;
+    }
+
+    Ok(ParseResult::Complete(command, pos))
+}
+
+/// 读取到 `\r\n` 为止的一行（不含 `\r\n` 本身），并把 `pos` 推进到行尾之后；
+/// 缓冲区中还没有完整一行时返回 `None`，`pos` 保持不变。
+fn read_line<'a>(buf: &'a [u8], pos: &mut usize) -> Option<&'a [u8]> {
+    let rest = &buf[*pos..];
+    let idx = rest.windows(2).position(|w| w == b"\r\n")?;
+    let line = &rest[..idx];
+    *pos += idx + 2;
+    Some(line)
+}
+
+fn parse_usize(bytes: &[u8]) -> Result<usize, ProtocolError> {
+    std::str::from_utf8(bytes)
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .ok_or_else(|| ProtocolError(format!("invalid length {:?}", bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_complete_command() {
+        let buf = b"*2\r\n$4\r\nECHO\r\n$2\r\nhi\r\n";
+        match parse(buf).unwrap() {
+            ParseResult::Complete(command, consumed) => {
+                assert_eq!(command, vec![b"ECHO".to_vec(), b"hi".to_vec()]);
+                assert_eq!(consumed, buf.len());
+            }
+            other => panic!("expected Complete, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reports_incomplete_when_split_across_reads() {
+        let buf = b"*2\r\n$4\r\nECHO\r\n$2\r\nh";
+        assert!(matches!(parse(buf).unwrap(), ParseResult::Incomplete));
+    }
+
+    #[test]
+    fn is_binary_safe_for_embedded_crlf() {
+        let value = b"foo\r\nbar";
+        let buf = format!("*1\r\n${}\r\n", value.len()).into_bytes();
+        let mut buf = buf;
+        buf.extend_from_slice(value);
+        buf.extend_from_slice(b"\r\n");
+
+        match parse(&buf).unwrap() {
+            ParseResult::Complete(command, consumed) => {
+                assert_eq!(command, vec![value.to_vec()]);
+                assert_eq!(consumed, buf.len());
+            }
+            other => panic!("expected Complete, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_zero_argument_arrays_as_an_empty_command() {
+        let buf = b"*0\r\n";
+        match parse(buf).unwrap() {
+            ParseResult::Complete(command, consumed) => {
+                assert!(command.is_empty());
+                assert_eq!(consumed, buf.len());
+            }
+            other => panic!("expected Complete, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_length() {
+        let buf = b"*2\r\n$notanumber\r\nx\r\n";
+        assert!(parse(buf).is_err());
+    }
+
+    #[test]
+    fn rejects_multibulk_length_over_the_cap() {
+        let buf = b"*99999999999\r\n";
+        assert!(parse(buf).is_err());
+    }
+
+    #[test]
+    fn rejects_bulk_length_over_the_cap() {
+        let buf = b"*1\r\n$99999999999\r\n";
+        assert!(parse(buf).is_err());
+    }
+}
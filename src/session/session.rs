@@ -0,0 +1,70 @@
+use std::sync::mpsc::Sender;
+
+/*
+ * 客户端会话
+ *
+ * @param authenticated 是否已通过 AUTH 校验
+ * @param selected_database 当前 SELECT 的数据库编号
+ * @param publish_sender 本连接写线程的另一端；SUBSCRIBE 时把它交给
+ *        `PubSub`，使得其他连接的 PUBLISH 能把消息推到这个连接上
+ * @param subscriptions 当前连接订阅的频道集合
+ */
+pub struct Session {
+    authenticated: bool,
+    selected_database: usize,
+    publish_sender: Option<Sender<Vec<u8>>>,
+    subscriptions: Vec<String>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self {
+            authenticated: false,
+            selected_database: 0,
+            publish_sender: None,
+            subscriptions: Vec::new(),
+        }
+    }
+
+    pub fn add_subscription(&mut self, channel: String) {
+        if !self.subscriptions.contains(&channel) {
+            self.subscriptions.push(channel);
+        }
+    }
+
+    pub fn remove_subscription(&mut self, channel: &str) {
+        self.subscriptions.retain(|c| c != channel);
+    }
+
+    pub fn subscription_count(&self) -> usize {
+        self.subscriptions.len()
+    }
+
+    pub fn subscriptions(&self) -> Vec<String> {
+        self.subscriptions.clone()
+    }
+
+    pub fn set_publish_sender(&mut self, sender: Sender<Vec<u8>>) {
+        self.publish_sender = Some(sender);
+    }
+
+    pub fn get_publish_sender(&self) -> Option<Sender<Vec<u8>>> {
+        self.publish_sender.clone()
+    }
+
+    pub fn get_authenticated(&self) -> bool {
+        self.authenticated
+    }
+
+    pub fn set_authenticated(&mut self, authenticated: bool) {
+        self.authenticated = authenticated;
+    }
+
+    pub fn get_selected_database(&self) -> usize {
+        self.selected_database
+    }
+
+    pub fn set_selected_database(&mut self, db_index: usize) {
+        self.selected_database = db_index;
+    }
+}
@@ -0,0 +1,136 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rustls::{Certificate, PrivateKey, ServerConfig, ServerConnection, StreamOwned};
+
+/*
+ * 客户端连接的运行时类型
+ *
+ * 明文场景下就是原来的 `TcpStream`；TLS 场景下套了一层 rustls 的
+ * `StreamOwned`，读写都要先过加解密，握手本身也是惰性地在第一次
+ * 读写时由 `StreamOwned` 内部完成的，不需要额外一步。统一成这一个
+ * 枚举，使得 `CommandStrategy` 的实现不用关心连接是否加密，和原来
+ * 按 `TcpStream` 写的代码基本不用改。
+ *
+ * 一条 TLS 会话的加解密状态只有一份，`subscribe`/`sync` 这类命令需要
+ * 把连接的写端单独交给一条后台线程，因此 TLS 分支包一层
+ * `Arc<Mutex<..>>` 共享同一个 `StreamOwned`，而不是像明文那样
+ * `TcpStream::try_clone` 出两个独立的文件描述符。
+ *
+ * 共享同一把锁意味着 `read` 不能像明文那样一直阻塞在锁里——那样的话，
+ * 连接空闲时读线程会永远攥着锁，写线程在 `write_all` 里就再也等不到
+ * 锁，PUBLISH/SYNC 转发全都会被无限期地憋住。所以 `accept` 给底层
+ * socket 设置了一个短超时，`read` 遇到超时就先放锁再重试，让写线程
+ * 能在两次重试之间插进来拿到锁。
+ */
+pub enum ClientStream {
+    Plain(TcpStream),
+    Tls(Arc<Mutex<StreamOwned<ServerConnection, TcpStream>>>),
+}
+
+impl ClientStream {
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            ClientStream::Plain(stream) => stream.peer_addr(),
+            ClientStream::Tls(stream) => stream.lock().unwrap().sock.peer_addr(),
+        }
+    }
+
+    /// 克隆出一份可以独立读写的句柄：明文直接 `TcpStream::try_clone`，
+    /// TLS 则共享同一个 `Arc<Mutex<StreamOwned<..>>>`。
+    pub fn try_clone(&self) -> io::Result<ClientStream> {
+        match self {
+            ClientStream::Plain(stream) => Ok(ClientStream::Plain(stream.try_clone()?)),
+            ClientStream::Tls(stream) => Ok(ClientStream::Tls(Arc::clone(stream))),
+        }
+    }
+}
+
+impl Read for ClientStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            ClientStream::Plain(stream) => stream.read(buf),
+            ClientStream::Tls(stream) => loop {
+                match stream.lock().unwrap().read(buf) {
+                    Err(e)
+                        if e.kind() == io::ErrorKind::WouldBlock
+                            || e.kind() == io::ErrorKind::TimedOut =>
+                    {
+                        // 锁已经释放，给写线程一个抢锁推送消息的窗口，再继续等数据。
+                        continue;
+                    }
+                    result => return result,
+                }
+            },
+        }
+    }
+}
+
+impl Write for ClientStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            ClientStream::Plain(stream) => stream.write(buf),
+            ClientStream::Tls(stream) => stream.lock().unwrap().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            ClientStream::Plain(stream) => stream.flush(),
+            ClientStream::Tls(stream) => stream.lock().unwrap().flush(),
+        }
+    }
+}
+
+/// 从 PEM 编码的证书/私钥文件构建 rustls 服务端配置，TLS 监听器启动时
+/// 加载一次，之后每条新连接握手都复用同一份配置。
+pub fn load_server_config(cert_path: &str, key_path: &str) -> Arc<ServerConfig> {
+    let certs = load_certs(cert_path);
+    let key = load_private_key(key_path);
+
+    let config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .expect("invalid TLS certificate/key pair");
+
+    Arc::new(config)
+}
+
+fn load_certs(path: &str) -> Vec<Certificate> {
+    let file = File::open(path).expect("failed to open TLS cert file");
+    let mut reader = BufReader::new(file);
+    rustls_pemfile::certs(&mut reader)
+        .expect("failed to parse TLS cert file")
+        .into_iter()
+        .map(Certificate)
+        .collect()
+}
+
+fn load_private_key(path: &str) -> PrivateKey {
+    let file = File::open(path).expect("failed to open TLS key file");
+    let mut reader = BufReader::new(file);
+    let keys = rustls_pemfile::pkcs8_private_keys(&mut reader).expect("failed to parse TLS key file");
+    PrivateKey(
+        keys.into_iter()
+            .next()
+            .expect("no private key found in TLS key file"),
+    )
+}
+
+/// 把刚 accept 到的明文 TCP 连接升级成 TLS 会话，返回的 `ClientStream`
+/// 可以像普通连接一样直接交给 `connection` 读写。
+///
+/// 给底层 socket 设一个短读超时，这样共享同一把锁的 `read` 不会无限期
+/// 攥住它——超时后锁会被释放，等着推送消息的写线程才有机会插进来。
+pub fn accept(stream: TcpStream, config: Arc<ServerConfig>) -> io::Result<ClientStream> {
+    stream.set_read_timeout(Some(Duration::from_millis(200)))?;
+
+    let connection =
+        ServerConnection::new(config).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    let tls_stream = StreamOwned::new(connection, stream);
+    Ok(ClientStream::Tls(Arc::new(Mutex::new(tls_stream))))
+}